@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{ensure, Context, Result};
+use serde::Deserialize;
+
+/// Layered config read from `dockerize.toml` at the project root. CLI flags
+/// always take precedence over values found here; this just saves having to
+/// repeat a long `cargo dockerize` invocation for every build.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DockerizeConfig {
+    /// Extra OCI/custom labels merged in alongside the fixed set of labels
+    /// `main` already knows how to build from CLI flags/manifest metadata.
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+
+    #[serde(default, rename = "target")]
+    pub targets: HashMap<String, TargetConfig>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TargetConfig {
+    /// Alternate Dockerfile to use for this target triple.
+    pub dockerfile: Option<String>,
+    /// Alternate build context directory for this target triple.
+    pub context: Option<String>,
+    #[serde(default, rename = "build-args")]
+    pub build_args: HashMap<String, String>,
+    #[serde(default, rename = "pre-build")]
+    pub pre_build: Vec<String>,
+}
+
+impl DockerizeConfig {
+    pub fn target(&self, triple: &str) -> Option<&TargetConfig> {
+        self.targets.get(triple)
+    }
+}
+
+/// Loads `dockerize.toml` from `project_root`. Returns the default (empty)
+/// config if the file doesn't exist.
+pub fn load_config(project_root: &Path) -> Result<DockerizeConfig> {
+    let path = project_root.join("dockerize.toml");
+    if !path.exists() {
+        return Ok(DockerizeConfig::default());
+    }
+
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Runs each `pre-build` shell command in order, in `project_root`, failing
+/// on the first non-zero exit.
+pub fn run_pre_build_hooks(commands: &[String], project_root: &Path) -> Result<()> {
+    for command in commands {
+        println!("Running pre-build hook: {command}");
+        let status = Command::new("sh")
+            .current_dir(project_root)
+            .arg("-c")
+            .arg(command)
+            .status()
+            .with_context(|| format!("Failed to execute pre-build hook: {command}"))?;
+        ensure!(status.success(), "pre-build hook failed: {command}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn target_looks_up_by_triple() {
+        let mut config = DockerizeConfig::default();
+        config.targets.insert(
+            "x86_64-unknown-linux-gnu".to_string(),
+            TargetConfig {
+                dockerfile: Some("Dockerfile.amd64".to_string()),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            config.target("x86_64-unknown-linux-gnu").and_then(|t| t.dockerfile.as_deref()),
+            Some("Dockerfile.amd64")
+        );
+        assert!(config.target("aarch64-unknown-linux-gnu").is_none());
+    }
+
+    #[test]
+    fn parses_labels_and_per_target_overrides() {
+        let toml = r#"
+            [labels]
+            team = "platform"
+
+            [target.x86_64-unknown-linux-gnu]
+            dockerfile = "Dockerfile.amd64"
+            context = "."
+            build-args = { FOO = "bar" }
+            pre-build = ["echo hi"]
+        "#;
+
+        let config: DockerizeConfig = toml::from_str(toml).unwrap();
+        assert_eq!(config.labels.get("team").map(String::as_str), Some("platform"));
+
+        let target = config.target("x86_64-unknown-linux-gnu").unwrap();
+        assert_eq!(target.dockerfile.as_deref(), Some("Dockerfile.amd64"));
+        assert_eq!(target.build_args.get("FOO").map(String::as_str), Some("bar"));
+        assert_eq!(target.pre_build, vec!["echo hi".to_string()]);
+    }
+
+    #[test]
+    fn load_config_returns_default_when_file_missing() {
+        let dir = std::env::temp_dir()
+            .join(format!("cargo-dockerize-test-missing-{}", std::process::id()));
+
+        let config = load_config(&dir).unwrap();
+        assert!(config.labels.is_empty());
+        assert!(config.targets.is_empty());
+    }
+
+    #[test]
+    fn load_config_reads_dockerize_toml() {
+        let dir = std::env::temp_dir().join(format!("cargo-dockerize-test-load-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("dockerize.toml"), "[labels]\nteam = \"platform\"\n").unwrap();
+
+        let config = load_config(&dir).unwrap();
+        assert_eq!(config.labels.get("team").map(String::as_str), Some("platform"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,248 @@
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::registry::RegistryError;
+
+/// A container CLI (Docker, Podman, nerdctl, ...) capable of building,
+/// saving, pushing, tagging, and inspecting images. All three support
+/// Docker's CLI surface closely enough that a single trait covers them;
+/// only the binary name differs.
+pub trait ContainerEngine {
+    /// Name shown in logs and used to select this engine via `--engine`.
+    fn name(&self) -> &'static str;
+
+    /// The executable to invoke (`docker`, `podman`, `nerdctl`, ...).
+    fn binary(&self) -> &str;
+
+    fn build(&self, build_args: &[String], cwd: &Path) -> Result<()> {
+        let status = Command::new(self.binary())
+            .current_dir(cwd)
+            .args(build_args)
+            .status()
+            .with_context(|| format!("Failed to execute {} build", self.binary()))?;
+        anyhow::ensure!(status.success(), "{} build failed", self.binary());
+        Ok(())
+    }
+
+    fn tag(&self, local: &str, remote: &str) -> Result<()> {
+        let status = Command::new(self.binary())
+            .args(["tag", local, remote])
+            .status()
+            .with_context(|| format!("Failed to execute {} tag", self.binary()))?;
+        anyhow::ensure!(status.success(), "{} tag failed for {local} -> {remote}", self.binary());
+        Ok(())
+    }
+
+    fn push(&self, remote: &str) -> Result<(), RegistryError> {
+        let status = Command::new(self.binary()).args(["push", remote]).status()?;
+        if !status.success() {
+            return Err(RegistryError::PushFailed(status));
+        }
+        Ok(())
+    }
+
+    /// Builds (without running) the `<engine> save <image>` command, so callers
+    /// can pipe and spawn it and stream its stdout (e.g. into a gzip encoder)
+    /// instead of buffering it.
+    fn save_command(&self, image: &str) -> Command {
+        let mut command = Command::new(self.binary());
+        command.args(["save", image]);
+        command
+    }
+
+    /// Runs `<engine> image inspect --format '{{json .}}' <image>` and
+    /// parses the result.
+    ///
+    /// The format argument must be passed as-is, with no surrounding
+    /// quotes added around `{{json .}}` — `Command` does not go through a
+    /// shell, so literal quote characters would become part of the Go
+    /// template and the engine would emit a JSON *string* instead of an
+    /// object, breaking the parse below.
+    fn inspect(&self, image: &str) -> Result<ImageInspect> {
+        let output = Command::new(self.binary())
+            .args(["image", "inspect", "--format", "{{json .}}", image])
+            .output()
+            .with_context(|| format!("Failed to execute {} image inspect", self.binary()))?;
+
+        if !output.status.success() {
+            bail!(
+                "{} image inspect failed: {}",
+                self.binary(),
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        let raw: RawImageInspect = serde_json::from_slice(&output.stdout)
+            .context("Failed to parse image inspect JSON")?;
+
+        Ok(ImageInspect {
+            digest: raw
+                .repo_digests
+                .first()
+                .and_then(|d| d.split_once('@').map(|(_, digest)| digest.to_string()))
+                .unwrap_or(raw.id),
+            size: raw.size,
+            layer_count: raw.root_fs.layers.len(),
+        })
+    }
+}
+
+/// The subset of `docker image inspect` we care about after a build: final
+/// digest, on-disk size, and layer count.
+#[derive(Debug, Clone)]
+pub struct ImageInspect {
+    pub digest: String,
+    pub size: u64,
+    pub layer_count: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawImageInspect {
+    #[serde(rename = "Id")]
+    id: String,
+    #[serde(rename = "RepoDigests", default)]
+    repo_digests: Vec<String>,
+    #[serde(rename = "Size")]
+    size: u64,
+    #[serde(rename = "RootFS")]
+    root_fs: RawRootFs,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawRootFs {
+    #[serde(rename = "Layers", default)]
+    layers: Vec<String>,
+}
+
+pub struct Docker;
+pub struct Podman;
+pub struct Nerdctl;
+
+impl ContainerEngine for Docker {
+    fn name(&self) -> &'static str {
+        "docker"
+    }
+
+    fn binary(&self) -> &str {
+        "docker"
+    }
+}
+
+impl ContainerEngine for Podman {
+    fn name(&self) -> &'static str {
+        "podman"
+    }
+
+    fn binary(&self) -> &str {
+        "podman"
+    }
+}
+
+impl ContainerEngine for Nerdctl {
+    fn name(&self) -> &'static str {
+        "nerdctl"
+    }
+
+    fn binary(&self) -> &str {
+        "nerdctl"
+    }
+}
+
+/// Selects a `ContainerEngine` from an explicit `--engine` value, or by
+/// auto-detecting from `$CONTAINER_RUNTIME`, `$DOCKER`, then whichever of
+/// docker/podman/nerdctl is first found on `$PATH`.
+pub fn select_engine(explicit: Option<&str>) -> Result<Box<dyn ContainerEngine>> {
+    if let Some(name) = explicit {
+        return engine_by_name(name);
+    }
+
+    if let Ok(name) = env::var("CONTAINER_RUNTIME") {
+        return engine_by_name(&name);
+    }
+
+    if let Ok(name) = env::var("DOCKER") {
+        return engine_by_name(&name);
+    }
+
+    for candidate in ["docker", "podman", "nerdctl"] {
+        if which(candidate) {
+            return engine_by_name(candidate);
+        }
+    }
+
+    bail!("Could not find docker, podman, or nerdctl on PATH; pass --engine explicitly")
+}
+
+fn engine_by_name(name: &str) -> Result<Box<dyn ContainerEngine>> {
+    match name {
+        "docker" => Ok(Box::new(Docker)),
+        "podman" => Ok(Box::new(Podman)),
+        "nerdctl" => Ok(Box::new(Nerdctl)),
+        other => bail!("Unknown container engine `{other}` (expected docker, podman, or nerdctl)"),
+    }
+}
+
+fn which(binary: &str) -> bool {
+    let Ok(path_var) = env::var("PATH") else {
+        return false;
+    };
+
+    env::split_paths(&path_var).any(|dir| dir.join(binary).is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    // `select_engine`'s env-var branches read process-global state, so
+    // serialize the tests that touch them to avoid cross-test races under
+    // `cargo test`'s default parallel runner.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn engine_by_name_resolves_known_engines() {
+        assert_eq!(engine_by_name("docker").unwrap().name(), "docker");
+        assert_eq!(engine_by_name("podman").unwrap().name(), "podman");
+        assert_eq!(engine_by_name("nerdctl").unwrap().name(), "nerdctl");
+    }
+
+    #[test]
+    fn engine_by_name_rejects_unknown_engine() {
+        assert!(engine_by_name("buildah").is_err());
+    }
+
+    #[test]
+    fn explicit_engine_takes_precedence_over_env() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        env::set_var("CONTAINER_RUNTIME", "nerdctl");
+        let result = select_engine(Some("podman"));
+        env::remove_var("CONTAINER_RUNTIME");
+        assert_eq!(result.unwrap().name(), "podman");
+    }
+
+    #[test]
+    fn container_runtime_env_takes_precedence_over_docker_env() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        env::set_var("CONTAINER_RUNTIME", "podman");
+        env::set_var("DOCKER", "nerdctl");
+        let result = select_engine(None);
+        env::remove_var("CONTAINER_RUNTIME");
+        env::remove_var("DOCKER");
+        assert_eq!(result.unwrap().name(), "podman");
+    }
+
+    #[test]
+    fn docker_env_used_when_container_runtime_unset() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        env::remove_var("CONTAINER_RUNTIME");
+        env::set_var("DOCKER", "nerdctl");
+        let result = select_engine(None);
+        env::remove_var("DOCKER");
+        assert_eq!(result.unwrap().name(), "nerdctl");
+    }
+}
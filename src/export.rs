@@ -0,0 +1,43 @@
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::process::Stdio;
+
+use anyhow::{bail, Context, Result};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use crate::engine::ContainerEngine;
+
+/// Streams `<engine> save <image>` (a tar stream) through a Rust gzip
+/// encoder into `output_path`, without shelling out to `sh -c` or an
+/// external `gzip`. The child's stdout is read incrementally so large
+/// images never need to be buffered fully in memory.
+pub fn export_tgz(
+    engine: &dyn ContainerEngine,
+    image: &str,
+    output_path: &Path,
+    compression_level: u32,
+) -> Result<()> {
+    let mut child = engine
+        .save_command(image)
+        .stdout(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to execute {} save", engine.binary()))?;
+
+    let mut stdout = child.stdout.take().expect("piped stdout");
+
+    let output_file = File::create(output_path)
+        .with_context(|| format!("Failed to create {}", output_path.display()))?;
+    let mut encoder = GzEncoder::new(output_file, Compression::new(compression_level));
+
+    io::copy(&mut stdout, &mut encoder).context("Failed to stream image save output into gzip")?;
+    encoder.finish().context("Failed to finalize gzip archive")?;
+
+    let status = child.wait().context("Failed to wait on image save")?;
+    if !status.success() {
+        bail!("{} save failed with {}", engine.binary(), status);
+    }
+
+    Ok(())
+}
@@ -1,11 +1,24 @@
 use std::path::{Path, PathBuf};
-use std::process::{Command, exit};
+use std::process::Command;
 use std::env;
-use std::fs;
 use anyhow::{Result, Context, bail};
-use clap::{Parser, Subcommand};
+use clap::Parser;
 use chrono::Utc;
 
+mod config;
+mod engine;
+mod export;
+mod metadata;
+mod registry;
+mod targets;
+mod versioning;
+
+use config::load_config;
+use engine::select_engine;
+use versioning::BumpLevel;
+use metadata::get_cargo_metadata;
+use targets::{apply_cargo_target_args, buildx_available, docker_platform_for_triple, ensure_buildx_builder};
+
 #[derive(Parser)]
 #[command(name = "cargo")]
 #[command(bin_name = "cargo")]
@@ -20,6 +33,14 @@ struct Dockerize {
     /// Export the Docker image as a TGZ archive
     #[arg(short, long)]
     export: bool,
+
+    /// Gzip compression level (0-9) used when exporting
+    #[arg(long, default_value_t = 6, value_parser = clap::value_parser!(u32).range(0..=9))]
+    compression: u32,
+
+    /// Output path for the exported TGZ archive (defaults to <name>-<tag>.tgz in the project root)
+    #[arg(long)]
+    output: Option<PathBuf>,
     
     /// Name of the Docker image (defaults to the package name)
     #[arg(short, long)]
@@ -29,9 +50,10 @@ struct Dockerize {
     #[arg(short, long)]
     tag: Option<String>,
     
-    /// Path to the Dockerfile (defaults to ./Dockerfile)
-    #[arg(long, default_value = "Dockerfile")]
-    dockerfile: String,
+    /// Path to the Dockerfile (defaults to ./Dockerfile, or the dockerize.toml
+    /// per-target dockerfile if one applies)
+    #[arg(long)]
+    dockerfile: Option<String>,
     
     /// Additional tags for the Docker image
     #[arg(long, value_delimiter = ',')]
@@ -68,6 +90,51 @@ struct Dockerize {
     /// OCI Image licenses
     #[arg(long)]
     licenses: Option<String>,
+
+    /// Package to dockerize, for workspaces with more than one member
+    #[arg(long)]
+    package: Option<String>,
+
+    /// Rust target triple to cross-compile for (e.g. aarch64-unknown-linux-gnu)
+    #[arg(long)]
+    target: Option<String>,
+
+    /// Comma-separated list of Docker platforms to build for (e.g. linux/amd64,linux/arm64).
+    /// When more than one platform is given, builds via `docker buildx` and emits a
+    /// multi-arch image index instead of a single-arch image.
+    #[arg(long, value_delimiter = ',')]
+    platform: Vec<String>,
+
+    /// Pass `-Z build-std` to cargo, for tier-3 targets without a prebuilt std
+    #[arg(long)]
+    build_std: bool,
+
+    /// Push the built image (and all its tags) to a remote registry
+    #[arg(long)]
+    push: bool,
+
+    /// Remote registry host to push to (e.g. 123456789.dkr.ecr.us-east-1.amazonaws.com)
+    #[arg(long)]
+    registry: Option<String>,
+
+    /// Container engine to use (docker, podman, nerdctl). Auto-detected from
+    /// $CONTAINER_RUNTIME, $DOCKER, then $PATH if not given.
+    #[arg(long)]
+    engine: Option<String>,
+
+    /// Derive the image tag from repository state instead of --tag/manifest version.
+    /// Currently the only supported mode is "git".
+    #[arg(long = "tag-from")]
+    tag_from: Option<String>,
+
+    /// Bump the manifest version in-memory and use the result as the image's
+    /// primary tag plus a `latest` alias. Does not modify Cargo.toml.
+    #[arg(long, value_enum, conflicts_with = "tag_from")]
+    bump: Option<BumpLevel>,
+
+    /// Allow tagging (--tag-from git, --bump) with uncommitted changes in the working tree
+    #[arg(long)]
+    force: bool,
 }
 
 fn main() -> Result<()> {
@@ -77,48 +144,189 @@ fn main() -> Result<()> {
     let project_root = find_project_root()?;
     println!("Project root: {}", project_root.display());
     
+    // Select the container engine (docker, podman, nerdctl)
+    let engine = select_engine(args.engine.as_deref())?;
+
+    // Load dockerize.toml, if present, and the section for --target (if any)
+    let dockerize_config = load_config(&project_root)?;
+    let target_config = args.target.as_deref().and_then(|t| dockerize_config.target(t));
+
     // Read cargo metadata to get package info
-    let metadata = get_cargo_metadata(&project_root)?;
-    
-    // Determine image name and tag
-    let image_name = args.name.unwrap_or_else(|| metadata.0.clone());
-    let image_tag = args.tag.unwrap_or_else(|| metadata.1.clone());
+    let metadata = get_cargo_metadata(&project_root, args.package.as_deref())?;
+
+    // Determine image name and tag: --tag > --bump > --tag-from git > manifest version.
+    // --bump also applies a `latest` alias tag. --bump and --tag-from are mutually exclusive.
+    let image_name = args.name.clone().unwrap_or_else(|| metadata.name.clone());
+    let require_clean_tree = || -> Result<()> {
+        if !args.force && versioning::is_dirty(&project_root)? {
+            bail!("Working tree is dirty; commit your changes or pass --force to tag anyway");
+        }
+        Ok(())
+    };
+    let mut alias_tags: Vec<String> = Vec::new();
+    let image_tag = if let Some(tag) = &args.tag {
+        tag.clone()
+    } else if let Some(bump) = args.bump {
+        require_clean_tree()?;
+        alias_tags.push("latest".to_string());
+        versioning::bump_version(&metadata.version, bump)?.to_string()
+    } else if args.tag_from.as_deref() == Some("git") {
+        require_clean_tree()?;
+        versioning::tag_from_git(&project_root, &metadata.version)?
+    } else if let Some(mode) = &args.tag_from {
+        bail!("Unknown --tag-from mode `{mode}` (expected \"git\")");
+    } else {
+        metadata.version.clone()
+    };
     let image_full = format!("{}:{}", image_name, image_tag);
     
     // Get git revision if available
     let git_revision = get_git_revision(&project_root).unwrap_or_else(|_| String::from("unknown"));
     
+    // CLI flags override dockerize.toml, which overrides the built-in defaults
+    let dockerfile = args.dockerfile.clone()
+        .or_else(|| target_config.and_then(|t| t.dockerfile.clone()))
+        .unwrap_or_else(|| "Dockerfile".to_string());
+    let build_context = target_config
+        .and_then(|t| t.context.clone())
+        .unwrap_or_else(|| ".".to_string());
+
     // Verify Dockerfile exists
-    let dockerfile_path = project_root.join(&args.dockerfile);
+    let dockerfile_path = project_root.join(&dockerfile);
     if !dockerfile_path.exists() {
         bail!("Dockerfile not found at: {}", dockerfile_path.display());
     }
-    
-    // Build the Rust project
+
+    // Run any configured pre-build hooks before compiling
+    if let Some(target_config) = target_config {
+        config::run_pre_build_hooks(&target_config.pre_build, &project_root)?;
+    }
+
+    // Build the Rust project, optionally cross-compiling for --target
     println!("Building Rust project...");
+    let mut cargo_build_args = vec!["build".to_string(), "--release".to_string()];
+    apply_cargo_target_args(&mut cargo_build_args, args.target.as_deref(), args.build_std);
+
     let build_status = Command::new("cargo")
         .current_dir(&project_root)
-        .args(["build", "--release"])
+        .args(&cargo_build_args)
         .status()
         .context("Failed to execute cargo build")?;
-        
+
     if !build_status.success() {
         bail!("Cargo build failed");
     }
-    
+
+    // `--platform` with more than one entry needs a multi-arch image index,
+    // which only `docker buildx build` can produce.
+    let use_buildx = args.platform.len() > 1;
+    if use_buildx {
+        if engine.name() != "docker" {
+            bail!("--platform with multiple platforms requires the docker engine (buildx)");
+        }
+        if !buildx_available() {
+            bail!("--platform with multiple platforms requires `docker buildx`, but it is not available");
+        }
+        // A multi-platform buildx build produces a manifest list that buildx
+        // cannot load into the local engine, only push straight to a
+        // registry. Without --push there is nowhere for the result to go,
+        // so require it up front instead of failing deep inside `docker
+        // buildx build` (or silently discarding the build).
+        anyhow::ensure!(
+            args.push,
+            "--platform with multiple platforms requires --push: buildx has no local multi-platform image to load, so the result must be pushed directly to a registry"
+        );
+        anyhow::ensure!(
+            !args.export,
+            "--export is not supported together with multiple --platform values: buildx doesn't produce a local image to export"
+        );
+        ensure_buildx_builder("cargo-dockerize")?;
+    }
+
+    // For a buildx multi-platform build, resolve the push registry and log in
+    // *before* building, since the build itself pushes via `--push` instead
+    // of going through the local-tag-then-push path used below.
+    let buildx_registry = if use_buildx {
+        let registry = args.registry.as_deref().context("--push requires --registry <host>")?;
+        if registry::is_ecr_registry(registry) {
+            println!("Authenticating with ECR registry: {}...", registry);
+            registry::ecr_login(engine.binary(), registry)?;
+            registry::ensure_ecr_repository(&image_name)?;
+        }
+        Some(registry)
+    } else {
+        None
+    };
+
+    // Qualifies `image_name:tag` with the buildx push registry, or leaves it
+    // as a local ref for a plain `docker build`.
+    let build_tag_ref = |tag: &str| match buildx_registry {
+        Some(registry) => registry::remote_ref(&image_name, tag, registry),
+        None => format!("{}:{}", image_name, tag),
+    };
+
     // Prepare Docker build command with OCI labels
-    let mut docker_build_args = vec![
-        "build".to_string(),
-        "-t".to_string(),
-        image_full.clone(),
-        "-f".to_string(),
-        args.dockerfile.clone(),
-    ];
-    
-    // Add additional tags if specified
-    for tag in &args.tags {
+    let mut docker_build_args = Vec::new();
+    if use_buildx {
+        docker_build_args.push("buildx".to_string());
+        docker_build_args.push("build".to_string());
+        docker_build_args.push("--platform".to_string());
+        docker_build_args.push(args.platform.join(","));
+        docker_build_args.push("--push".to_string());
+    } else {
+        docker_build_args.push("build".to_string());
+        // BuildKit's plain `docker build` also understands a single `--platform`;
+        // without it, a lone --platform value would be stamped onto build-args
+        // and labels below but the image would still be built for the host arch.
+        if let Some(platform) = args.platform.first() {
+            docker_build_args.push("--platform".to_string());
+            docker_build_args.push(platform.clone());
+        }
+    }
+    docker_build_args.push("-t".to_string());
+    docker_build_args.push(build_tag_ref(&image_tag));
+    docker_build_args.push("-f".to_string());
+    docker_build_args.push(dockerfile.clone());
+
+    // Derive a single target platform from --target or a lone --platform, and
+    // surface it to the Dockerfile as TARGETPLATFORM/TARGETARCH build-args.
+    // Never do this for a multi-platform buildx build: BuildKit already
+    // auto-populates TARGETPLATFORM/TARGETARCH per leg of the matrix, and an
+    // explicit --build-arg on the command line would override that for every
+    // platform, not just the first.
+    let single_platform = if use_buildx {
+        None
+    } else {
+        args.target.as_deref()
+            .and_then(docker_platform_for_triple)
+            .map(str::to_string)
+            .or_else(|| args.platform.first().cloned())
+    };
+    if let Some(platform) = &single_platform {
+        if let Some((_, arch, variant)) = targets::split_platform(platform) {
+            docker_build_args.push("--build-arg".to_string());
+            docker_build_args.push(format!("TARGETPLATFORM={}", platform));
+            docker_build_args.push("--build-arg".to_string());
+            docker_build_args.push(format!("TARGETARCH={}", arch));
+            if let Some(variant) = variant {
+                docker_build_args.push("--build-arg".to_string());
+                docker_build_args.push(format!("TARGETVARIANT={}", variant));
+            }
+        }
+    }
+
+    // Add per-target build-args from dockerize.toml
+    if let Some(target_config) = target_config {
+        for (key, value) in &target_config.build_args {
+            docker_build_args.push("--build-arg".to_string());
+            docker_build_args.push(format!("{}={}", key, value));
+        }
+    }
+
+    // Add additional tags if specified, plus any alias tags (e.g. `latest` from --bump)
+    for tag in args.tags.iter().chain(alias_tags.iter()) {
         docker_build_args.push("-t".to_string());
-        docker_build_args.push(format!("{}:{}", image_name, tag));
+        docker_build_args.push(build_tag_ref(tag));
     }
     
     // Add OCI labels
@@ -136,27 +344,34 @@ fn main() -> Result<()> {
         add_label(&mut docker_build_args, "org.opencontainers.image.title", &image_name);
     }
     
-    if let Some(desc) = &args.description {
+    let description = args.description.clone().or_else(|| metadata.description.clone());
+    if let Some(desc) = &description {
         add_label(&mut docker_build_args, "org.opencontainers.image.description", desc);
     }
-    
-    if let Some(authors) = &args.authors {
+
+    let authors = args.authors.clone().or_else(|| {
+        (!metadata.authors.is_empty()).then(|| metadata.authors.join(", "))
+    });
+    if let Some(authors) = &authors {
         add_label(&mut docker_build_args, "org.opencontainers.image.authors", authors);
     }
-    
-    if let Some(url) = &args.url {
+
+    let url = args.url.clone().or_else(|| metadata.homepage.clone());
+    if let Some(url) = &url {
         add_label(&mut docker_build_args, "org.opencontainers.image.url", url);
     }
-    
-    if let Some(source) = &args.source {
+
+    let source = args.source.clone().or_else(|| metadata.repository.clone());
+    if let Some(source) = &source {
         add_label(&mut docker_build_args, "org.opencontainers.image.source", source);
     }
-    
+
     if let Some(vendor) = &args.vendor {
         add_label(&mut docker_build_args, "org.opencontainers.image.vendor", vendor);
     }
-    
-    if let Some(licenses) = &args.licenses {
+
+    let licenses = args.licenses.clone().or_else(|| metadata.license.clone());
+    if let Some(licenses) = &licenses {
         add_label(&mut docker_build_args, "org.opencontainers.image.licenses", licenses);
     }
     
@@ -164,40 +379,77 @@ fn main() -> Result<()> {
     if let Some(app_name) = &args.application_name {
         add_label(&mut docker_build_args, "application_name", app_name);
     }
-    
+
+    // Add extra labels from dockerize.toml's top-level [labels] table, skipping
+    // any key already set by a CLI flag so the flag takes precedence
+    let cli_label_keys = [
+        (args.title.is_some(), "org.opencontainers.image.title"),
+        (args.description.is_some(), "org.opencontainers.image.description"),
+        (args.authors.is_some(), "org.opencontainers.image.authors"),
+        (args.url.is_some(), "org.opencontainers.image.url"),
+        (args.source.is_some(), "org.opencontainers.image.source"),
+        (args.vendor.is_some(), "org.opencontainers.image.vendor"),
+        (args.licenses.is_some(), "org.opencontainers.image.licenses"),
+        (args.application_name.is_some(), "application_name"),
+    ];
+    for (key, value) in &dockerize_config.labels {
+        if cli_label_keys.iter().any(|(set, cli_key)| *set && cli_key == key) {
+            continue;
+        }
+        add_label(&mut docker_build_args, key, value);
+    }
+
     // Add the build context
-    docker_build_args.push(".".to_string());
+    docker_build_args.push(build_context);
     
-    // Build Docker image
-    println!("Building Docker image: {}...", image_full);
-    let docker_build_status = Command::new("docker")
-        .current_dir(&project_root)
-        .args(&docker_build_args)
-        .status()
-        .context("Failed to execute docker build")?;
-        
-    if !docker_build_status.success() {
-        bail!("Docker build failed");
+    // Build image
+    println!("Building image with {}: {}...", engine.name(), image_full);
+    engine.build(&docker_build_args, &project_root)?;
+
+    if !use_buildx {
+        let inspected = engine.inspect(&image_full)?;
+        println!(
+            "Image digest: {} ({} bytes, {} layers)",
+            inspected.digest, inspected.size, inspected.layer_count
+        );
     }
-    
+
+    // Push to a remote registry if requested. A buildx multi-platform build
+    // already pushed the manifest list directly via `--push` above, so there
+    // is no local image left to tag and push here.
+    if args.push && !use_buildx {
+        let registry = args.registry.as_deref()
+            .context("--push requires --registry <host>")?;
+
+        if registry::is_ecr_registry(registry) {
+            println!("Authenticating with ECR registry: {}...", registry);
+            registry::ecr_login(engine.binary(), registry)?;
+            registry::ensure_ecr_repository(&image_name)?;
+        }
+
+        let mut local_tags = vec![image_tag.clone()];
+        local_tags.extend(args.tags.iter().cloned());
+        local_tags.extend(alias_tags.iter().cloned());
+
+        for tag in &local_tags {
+            let local = format!("{}:{}", image_name, tag);
+            let remote = registry::remote_ref(&image_name, tag, registry);
+            println!("Pushing {} -> {}...", local, remote);
+            engine.tag(&local, &remote)?;
+            engine.push(&remote)?;
+        }
+    }
+
     // Export to TGZ if requested
     if args.export {
-        let archive_name = format!("{}-{}.tgz", image_name, image_tag);
-        let archive_path = project_root.join(&archive_name);
-        println!("Exporting Docker image to: {}...", archive_path.display());
-        
-        let export_status = Command::new("sh")
-            .current_dir(&project_root)
-            .arg("-c")
-            .arg(format!("docker save {} | gzip > {}", image_full, archive_name))
-            .status()
-            .context("Failed to export Docker image")?;
-            
-        if !export_status.success() {
-            bail!("Docker export failed");
-        }
-        
-        println!("Docker image exported successfully to: {}", archive_path.display());
+        let archive_path = args.output.clone().unwrap_or_else(|| {
+            project_root.join(format!("{}-{}.tgz", image_name, image_tag))
+        });
+        println!("Exporting image to: {}...", archive_path.display());
+
+        export::export_tgz(engine.as_ref(), &image_full, &archive_path, args.compression)?;
+
+        println!("Image exported successfully to: {}", archive_path.display());
     }
     
     println!("Dockerize completed successfully!");
@@ -244,32 +496,3 @@ fn find_project_root() -> Result<PathBuf> {
         }
     }
 }
-
-// Get package name and version from Cargo.toml
-fn get_cargo_metadata(project_root: &Path) -> Result<(String, String)> {
-    let cargo_toml_path = project_root.join("Cargo.toml");
-    let cargo_toml_content = fs::read_to_string(cargo_toml_path)
-        .context("Failed to read Cargo.toml")?;
-    
-    let name_line = cargo_toml_content.lines()
-        .find(|line| line.trim().starts_with("name ="))
-        .context("Could not find package name in Cargo.toml")?;
-    
-    let version_line = cargo_toml_content.lines()
-        .find(|line| line.trim().starts_with("version ="))
-        .context("Could not find package version in Cargo.toml")?;
-    
-    let name = name_line.split('=').nth(1)
-        .context("Invalid name format in Cargo.toml")?
-        .trim()
-        .trim_matches('"')
-        .to_string();
-    
-    let version = version_line.split('=').nth(1)
-        .context("Invalid version format in Cargo.toml")?
-        .trim()
-        .trim_matches('"')
-        .to_string();
-    
-    Ok((name, version))
-}
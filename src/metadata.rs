@@ -0,0 +1,188 @@
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+/// Package-level information pulled out of `cargo metadata`, used both to
+/// pick default image name/tag and to auto-fill OCI labels.
+#[derive(Debug, Clone)]
+pub struct CargoMetadata {
+    pub name: String,
+    pub version: String,
+    pub description: Option<String>,
+    pub authors: Vec<String>,
+    pub repository: Option<String>,
+    pub homepage: Option<String>,
+    pub license: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MetadataDoc {
+    packages: Vec<PackageDoc>,
+    workspace_default_members: Option<Vec<String>>,
+    #[serde(default)]
+    workspace_members: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PackageDoc {
+    name: String,
+    version: String,
+    id: String,
+    description: Option<String>,
+    #[serde(default)]
+    authors: Vec<String>,
+    repository: Option<String>,
+    homepage: Option<String>,
+    license: Option<String>,
+}
+
+/// Run `cargo metadata` in `project_root` and select the package to build an
+/// image for.
+///
+/// Selection order:
+/// 1. `package` if given (the first package with that name, erroring if none matches).
+/// 2. The sole workspace default member (or sole workspace member, if none
+///    are marked default) — this also covers the common single-package,
+///    non-virtual-manifest case, since that's a workspace of one.
+///
+/// Anything else (workspace with multiple candidates and no `--package`) is
+/// an error, since there's no single image to build. Note this is run with
+/// `--no-deps`, so `resolve.root` is always null and can't be used here.
+pub fn get_cargo_metadata(project_root: &Path, package: Option<&str>) -> Result<CargoMetadata> {
+    let output = Command::new("cargo")
+        .current_dir(project_root)
+        .args(["metadata", "--format-version", "1", "--no-deps"])
+        .output()
+        .context("Failed to execute cargo metadata")?;
+
+    if !output.status.success() {
+        bail!(
+            "cargo metadata failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let doc: MetadataDoc =
+        serde_json::from_slice(&output.stdout).context("Failed to parse cargo metadata JSON")?;
+
+    let selected = select_package(&doc, package)?;
+
+    Ok(CargoMetadata {
+        name: selected.name.clone(),
+        version: selected.version.clone(),
+        description: selected.description.clone(),
+        authors: selected.authors.clone(),
+        repository: selected.repository.clone(),
+        homepage: selected.homepage.clone(),
+        license: selected.license.clone(),
+    })
+}
+
+fn select_package<'a>(doc: &'a MetadataDoc, package: Option<&str>) -> Result<&'a PackageDoc> {
+    if let Some(name) = package {
+        return doc
+            .packages
+            .iter()
+            .find(|p| p.name == name)
+            .with_context(|| format!("Package `{name}` not found in cargo metadata output"));
+    }
+
+    // Fall back to the workspace's default members (or all members if none
+    // are marked default).
+    let candidates = match &doc.workspace_default_members {
+        Some(members) if !members.is_empty() => members,
+        _ => &doc.workspace_members,
+    };
+
+    match candidates.len() {
+        1 => {
+            let id = &candidates[0];
+            doc.packages
+                .iter()
+                .find(|p| &p.id == id)
+                .context("Default workspace member not found in cargo metadata output")
+        }
+        0 => bail!("No packages found in cargo metadata output"),
+        _ => bail!(
+            "Workspace has multiple packages; pass --package <name> to pick one to dockerize"
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str, id: &str) -> PackageDoc {
+        PackageDoc {
+            name: name.to_string(),
+            version: "1.0.0".to_string(),
+            id: id.to_string(),
+            description: None,
+            authors: Vec::new(),
+            repository: None,
+            homepage: None,
+            license: None,
+        }
+    }
+
+    #[test]
+    fn selects_explicit_package_by_name() {
+        let doc = MetadataDoc {
+            packages: vec![package("foo", "foo#1.0.0"), package("bar", "bar#1.0.0")],
+            workspace_default_members: None,
+            workspace_members: vec!["foo#1.0.0".to_string(), "bar#1.0.0".to_string()],
+        };
+
+        let selected = select_package(&doc, Some("bar")).unwrap();
+        assert_eq!(selected.name, "bar");
+    }
+
+    #[test]
+    fn errors_when_explicit_package_not_found() {
+        let doc = MetadataDoc {
+            packages: vec![package("foo", "foo#1.0.0")],
+            workspace_default_members: None,
+            workspace_members: vec!["foo#1.0.0".to_string()],
+        };
+
+        assert!(select_package(&doc, Some("missing")).is_err());
+    }
+
+    #[test]
+    fn falls_back_to_sole_default_member() {
+        let doc = MetadataDoc {
+            packages: vec![package("foo", "foo#1.0.0"), package("bar", "bar#1.0.0")],
+            workspace_default_members: Some(vec!["bar#1.0.0".to_string()]),
+            workspace_members: vec!["foo#1.0.0".to_string(), "bar#1.0.0".to_string()],
+        };
+
+        let selected = select_package(&doc, None).unwrap();
+        assert_eq!(selected.name, "bar");
+    }
+
+    #[test]
+    fn falls_back_to_sole_workspace_member_when_no_default_members() {
+        let doc = MetadataDoc {
+            packages: vec![package("foo", "foo#1.0.0")],
+            workspace_default_members: None,
+            workspace_members: vec!["foo#1.0.0".to_string()],
+        };
+
+        let selected = select_package(&doc, None).unwrap();
+        assert_eq!(selected.name, "foo");
+    }
+
+    #[test]
+    fn errors_on_multiple_candidates_without_package_flag() {
+        let doc = MetadataDoc {
+            packages: vec![package("foo", "foo#1.0.0"), package("bar", "bar#1.0.0")],
+            workspace_default_members: None,
+            workspace_members: vec!["foo#1.0.0".to_string(), "bar#1.0.0".to_string()],
+        };
+
+        assert!(select_package(&doc, None).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+use std::io::Write;
+use std::process::{Command, ExitStatus, Stdio};
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use thiserror::Error;
+
+/// Errors specific to authenticating with and pushing to a remote registry,
+/// kept distinct from `docker build` failures so CI can tell "the image is
+/// broken" apart from "the registry rejected us".
+#[derive(Debug, Error)]
+pub enum RegistryError {
+    #[error("Failed to obtain an ECR authorization token: {0}")]
+    AuthTokenError(String),
+
+    #[error("Failed to decode ECR authorization token as base64")]
+    Base64DecodeError(#[from] base64::DecodeError),
+
+    #[error("docker push failed with {0}")]
+    PushFailed(ExitStatus),
+
+    #[error("Failed to run registry command: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Rewrites a local `name:tag` image reference to point at `registry`, e.g.
+/// `myapp:1.0` + `123.dkr.ecr.us-east-1.amazonaws.com` ->
+/// `123.dkr.ecr.us-east-1.amazonaws.com/myapp:1.0`.
+pub fn remote_ref(image_name: &str, tag: &str, registry: &str) -> String {
+    format!("{registry}/{image_name}:{tag}")
+}
+
+/// Returns true if `registry` looks like an AWS ECR host
+/// (`<account>.dkr.ecr.<region>.amazonaws.com`).
+pub fn is_ecr_registry(registry: &str) -> bool {
+    registry.contains(".dkr.ecr.") && registry.ends_with(".amazonaws.com")
+}
+
+/// Logs in to an ECR registry by requesting a short-lived authorization
+/// token via the AWS CLI, base64-decoding the returned `user:pass`, and
+/// feeding the password to `docker login --password-stdin`.
+pub fn ecr_login(engine: &str, registry: &str) -> Result<(), RegistryError> {
+    let output = Command::new("aws")
+        .args([
+            "ecr",
+            "get-authorization-token",
+            "--output",
+            "text",
+            "--query",
+            "authorizationData[0].authorizationToken",
+        ])
+        .output()
+        .map_err(|e| RegistryError::AuthTokenError(e.to_string()))?;
+
+    if !output.status.success() {
+        return Err(RegistryError::AuthTokenError(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    let token = String::from_utf8(output.stdout)
+        .map_err(|e| RegistryError::AuthTokenError(e.to_string()))?
+        .trim()
+        .to_string();
+
+    let decoded = base64::engine::general_purpose::STANDARD.decode(token)?;
+    let decoded = String::from_utf8(decoded)
+        .map_err(|e| RegistryError::AuthTokenError(e.to_string()))?;
+    let (user, pass) = decoded
+        .split_once(':')
+        .ok_or_else(|| RegistryError::AuthTokenError("malformed user:pass in token".to_string()))?;
+
+    let mut login = Command::new(engine)
+        .args(["login", "--username", user, "--password-stdin", registry])
+        .stdin(Stdio::piped())
+        .spawn()?;
+
+    login
+        .stdin
+        .take()
+        .expect("piped stdin")
+        .write_all(pass.as_bytes())?;
+
+    let status = login.wait()?;
+
+    if !status.success() {
+        return Err(RegistryError::AuthTokenError(format!(
+            "docker login failed with {status}"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Creates the ECR repository for `image_name` if it doesn't already exist.
+pub fn ensure_ecr_repository(image_name: &str) -> Result<()> {
+    let describe = Command::new("aws")
+        .args(["ecr", "describe-repositories", "--repository-names", image_name])
+        .output()
+        .context("Failed to execute aws ecr describe-repositories")?;
+
+    if describe.status.success() {
+        return Ok(());
+    }
+
+    // Any other failure (throttling, wrong region, missing IAM permission,
+    // ...) means we don't actually know whether the repository is missing,
+    // so surface it instead of masking it behind a misleading "failed to
+    // create" error from the call below.
+    let stderr = String::from_utf8_lossy(&describe.stderr);
+    anyhow::ensure!(
+        stderr.contains("RepositoryNotFoundException"),
+        "aws ecr describe-repositories failed: {}",
+        stderr.trim()
+    );
+
+    let status = Command::new("aws")
+        .args(["ecr", "create-repository", "--repository-name", image_name])
+        .status()
+        .context("Failed to execute aws ecr create-repository")?;
+
+    anyhow::ensure!(status.success(), "Failed to create ECR repository `{image_name}`");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_ecr_hosts() {
+        assert!(is_ecr_registry("123456789012.dkr.ecr.us-east-1.amazonaws.com"));
+        assert!(is_ecr_registry("123456789012.dkr.ecr.eu-west-2.amazonaws.com"));
+    }
+
+    #[test]
+    fn rejects_non_ecr_hosts() {
+        assert!(!is_ecr_registry("docker.io"));
+        assert!(!is_ecr_registry("gcr.io"));
+        assert!(!is_ecr_registry("registry.example.com"));
+    }
+
+    #[test]
+    fn builds_remote_ref() {
+        assert_eq!(
+            remote_ref("myapp", "1.0", "123.dkr.ecr.us-east-1.amazonaws.com"),
+            "123.dkr.ecr.us-east-1.amazonaws.com/myapp:1.0"
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use std::process::Command;
+
+/// Maps a Rust target triple to the Docker/OCI platform string
+/// (`os/arch[/variant]`) used by `--platform` and the `TARGETPLATFORM` /
+/// `TARGETARCH` build-args.
+pub fn docker_platform_for_triple(triple: &str) -> Option<&'static str> {
+    match triple {
+        "x86_64-unknown-linux-gnu" | "x86_64-unknown-linux-musl" => Some("linux/amd64"),
+        "aarch64-unknown-linux-gnu" | "aarch64-unknown-linux-musl" => Some("linux/arm64"),
+        "armv7-unknown-linux-gnueabihf" => Some("linux/arm/v7"),
+        "i686-unknown-linux-gnu" => Some("linux/386"),
+        _ => None,
+    }
+}
+
+/// Splits a Docker platform string (`linux/arm64` or `linux/arm/v7`) into
+/// `(os, arch, variant)`. `variant` is `None` unless the platform carries a
+/// third segment, e.g. the `v7` in `linux/arm/v7`.
+pub fn split_platform(platform: &str) -> Option<(&str, &str, Option<&str>)> {
+    let mut parts = platform.splitn(3, '/');
+    let os = parts.next()?;
+    let arch = parts.next()?;
+    Some((os, arch, parts.next()))
+}
+
+/// Extends a `cargo build --release` argument list with `--target` and,
+/// for tier-3 targets, `-Z build-std`.
+pub fn apply_cargo_target_args(build_args: &mut Vec<String>, target: Option<&str>, build_std: bool) {
+    if build_std {
+        build_args.push("-Z".to_string());
+        build_args.push("build-std".to_string());
+    }
+
+    if let Some(target) = target {
+        build_args.push("--target".to_string());
+        build_args.push(target.to_string());
+    }
+}
+
+/// Checks whether `docker buildx` is available by running `docker buildx
+/// version`.
+pub fn buildx_available() -> bool {
+    Command::new("docker")
+        .args(["buildx", "version"])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Ensures a buildx builder instance named `name` exists, creating it if
+/// necessary, and selects it as the active builder via `docker buildx use`.
+pub fn ensure_buildx_builder(name: &str) -> anyhow::Result<()> {
+    let inspect_status = Command::new("docker")
+        .args(["buildx", "inspect", name])
+        .output()?
+        .status;
+
+    if !inspect_status.success() {
+        let create_status = Command::new("docker")
+            .args(["buildx", "create", "--name", name])
+            .status()?;
+        anyhow::ensure!(create_status.success(), "Failed to create buildx builder `{name}`");
+    }
+
+    let use_status = Command::new("docker")
+        .args(["buildx", "use", name])
+        .status()?;
+    anyhow::ensure!(use_status.success(), "Failed to select buildx builder `{name}`");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_known_triples_to_docker_platforms() {
+        assert_eq!(docker_platform_for_triple("x86_64-unknown-linux-gnu"), Some("linux/amd64"));
+        assert_eq!(docker_platform_for_triple("aarch64-unknown-linux-musl"), Some("linux/arm64"));
+        assert_eq!(docker_platform_for_triple("armv7-unknown-linux-gnueabihf"), Some("linux/arm/v7"));
+    }
+
+    #[test]
+    fn unknown_triple_maps_to_none() {
+        assert_eq!(docker_platform_for_triple("wasm32-unknown-unknown"), None);
+    }
+
+    #[test]
+    fn splits_platform_into_os_and_arch() {
+        assert_eq!(split_platform("linux/amd64"), Some(("linux", "amd64", None)));
+        assert_eq!(split_platform("linux/arm64"), Some(("linux", "arm64", None)));
+    }
+
+    #[test]
+    fn splits_platform_with_variant() {
+        assert_eq!(split_platform("linux/arm/v7"), Some(("linux", "arm", Some("v7"))));
+    }
+
+    #[test]
+    fn split_platform_rejects_missing_slash() {
+        assert_eq!(split_platform("linux"), None);
+    }
+}
@@ -0,0 +1,138 @@
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{ensure, Context, Result};
+use clap::ValueEnum;
+use semver::Version;
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum BumpLevel {
+    Major,
+    Minor,
+    Patch,
+}
+
+/// Returns true if `git status --porcelain` reports any pending changes.
+pub fn is_dirty(project_root: &Path) -> Result<bool> {
+    let output = Command::new("git")
+        .current_dir(project_root)
+        .args(["status", "--porcelain"])
+        .output()
+        .context("Failed to execute git status")?;
+
+    ensure!(output.status.success(), "git status failed");
+    Ok(!output.stdout.is_empty())
+}
+
+/// `git describe --tags --exact-match`, if HEAD is exactly on a tag.
+fn describe_exact_tag(project_root: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .current_dir(project_root)
+        .args(["describe", "--tags", "--exact-match"])
+        .output()
+        .ok()?;
+
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn commit_count(project_root: &Path) -> Result<u64> {
+    let output = Command::new("git")
+        .current_dir(project_root)
+        .args(["rev-list", "--count", "HEAD"])
+        .output()
+        .context("Failed to execute git rev-list")?;
+
+    ensure!(output.status.success(), "git rev-list failed");
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .context("Unexpected output from git rev-list --count")
+}
+
+fn short_revision(project_root: &Path) -> Result<String> {
+    let output = Command::new("git")
+        .current_dir(project_root)
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .context("Failed to execute git rev-parse")?;
+
+    ensure!(output.status.success(), "git rev-parse failed");
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Derives a tag from repository state: the exact tag if HEAD is on one,
+/// otherwise a prerelease tag of the form `<version>-<commit-count>.g<shorthash>`.
+pub fn tag_from_git(project_root: &Path, version: &str) -> Result<String> {
+    if let Some(tag) = describe_exact_tag(project_root) {
+        return Ok(tag);
+    }
+
+    let count = commit_count(project_root)?;
+    let hash = short_revision(project_root)?;
+    Ok(format!("{version}-{count}.g{hash}"))
+}
+
+/// Parses `version` as semver and increments the given component, clearing
+/// lower components and any prerelease/build metadata.
+pub fn bump_version(version: &str, level: BumpLevel) -> Result<Version> {
+    let mut parsed = Version::parse(version)
+        .with_context(|| format!("`{version}` is not a valid semver version"))?;
+
+    match level {
+        BumpLevel::Major => {
+            parsed.major += 1;
+            parsed.minor = 0;
+            parsed.patch = 0;
+        }
+        BumpLevel::Minor => {
+            parsed.minor += 1;
+            parsed.patch = 0;
+        }
+        BumpLevel::Patch => {
+            parsed.patch += 1;
+        }
+    }
+    parsed.pre = semver::Prerelease::EMPTY;
+    parsed.build = semver::BuildMetadata::EMPTY;
+
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bumps_major_and_clears_lower_components() {
+        let bumped = bump_version("1.2.3", BumpLevel::Major).unwrap();
+        assert_eq!(bumped, Version::new(2, 0, 0));
+    }
+
+    #[test]
+    fn bumps_minor_and_clears_patch() {
+        let bumped = bump_version("1.2.3", BumpLevel::Minor).unwrap();
+        assert_eq!(bumped, Version::new(1, 3, 0));
+    }
+
+    #[test]
+    fn bumps_patch() {
+        let bumped = bump_version("1.2.3", BumpLevel::Patch).unwrap();
+        assert_eq!(bumped, Version::new(1, 2, 4));
+    }
+
+    #[test]
+    fn bump_clears_prerelease_and_build_metadata() {
+        let bumped = bump_version("1.2.3-alpha.1+build.5", BumpLevel::Patch).unwrap();
+        assert_eq!(bumped, Version::new(1, 2, 4));
+        assert!(bumped.pre.is_empty());
+        assert!(bumped.build.is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_semver() {
+        assert!(bump_version("not-a-version", BumpLevel::Patch).is_err());
+    }
+}